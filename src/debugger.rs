@@ -0,0 +1,118 @@
+//! Interactive stepping debugger built on top of `ProgramRuntime::step`.
+//! Breakpoints can be set on IR indices explicitly, or baked into the source
+//! via the `#` Brainfuck debug convention (see `Program::new`'s `debug_mode`).
+
+use std::collections::HashSet;
+
+use super::{
+    BfError, BfRead, BfWrite, BrainfuckError, EofBehavior, Inst, PointerMode, Program, ProgramRuntime,
+    ProgramRuntimeError,
+};
+
+/// Wraps a `ProgramRuntime` with breakpoints and tape inspection for
+/// authoring non-trivial Brainfuck programs. Always runs over `u8` cells
+/// with [`PointerMode::Wrap`]; use [`super::eval_mem_with_opts`] directly if
+/// you need wider cells or a different pointer mode without the debugger.
+pub struct Debugger<R: BfRead, W: BfWrite> {
+    runtime: ProgramRuntime<R, W>,
+    code: Vec<Inst>,
+    mem: Vec<u8>,
+    mem_ptr: usize,
+    breakpoints: HashSet<usize>,
+}
+
+impl<R: BfRead, W: BfWrite> Debugger<R, W> {
+    /// Compiles `code` with `#` breakpoints enabled and prepares a debugger
+    /// over a fresh, zeroed tape of `mem_len` cells.
+    pub fn new(code: &[u8], mem_len: usize, input: R, output: W, eof_behavior: EofBehavior) -> Result<Self, BrainfuckError> {
+        let program = try!(Program::new(code, true));
+        let mut breakpoints = HashSet::new();
+        breakpoints.extend(program.breakpoints.iter().cloned());
+
+        Ok(Debugger {
+            runtime: ProgramRuntime::new(input, output, eof_behavior, PointerMode::Wrap),
+            code: program.code,
+            mem: vec![0u8; mem_len],
+            mem_ptr: 0,
+            breakpoints,
+        })
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn remove_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.remove(&pc);
+    }
+
+    pub fn pc(&self) -> usize {
+        self.runtime.pc.0
+    }
+
+    pub fn mem_ptr(&self) -> usize {
+        self.mem_ptr
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pc() >= self.code.len()
+    }
+
+    /// The instruction about to run, or `None` if the program has finished.
+    pub fn current_inst(&self) -> Option<Inst> {
+        self.code.get(self.pc()).cloned()
+    }
+
+    /// Executes a single IR instruction, returning the PC it ran at, or
+    /// `None` if the program had already finished. Flushes output afterwards,
+    /// so `.` output is visible immediately rather than only once the
+    /// program finishes or the session ends.
+    pub fn step(&mut self) -> Result<Option<usize>, ProgramRuntimeError> {
+        if self.is_finished() {
+            return Ok(None);
+        }
+        let pc = self.pc();
+        try!(self.runtime.step(&self.code, &mut self.mem, &mut self.mem_ptr));
+        try!(self.runtime.output.flush());
+        Ok(Some(pc))
+    }
+
+    /// Single-steps until a breakpoint PC is reached or the program ends,
+    /// returning the breakpoint PC hit, or `None` if the program ended first.
+    pub fn cont(&mut self) -> Result<Option<usize>, ProgramRuntimeError> {
+        while try!(self.step()).is_some() {
+            if self.breakpoints.contains(&self.pc()) {
+                return Ok(Some(self.pc()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns a window of cells around the memory pointer (`radius` either
+    /// side, clamped to the tape bounds) along with the pointer's offset
+    /// within that window.
+    pub fn tape_window(&self, radius: usize) -> (&[u8], usize) {
+        let start = self.mem_ptr.saturating_sub(radius);
+        let end = std::cmp::min(self.mem.len(), self.mem_ptr + radius + 1);
+        (&self.mem[start..end], self.mem_ptr - start)
+    }
+
+    /// Reads a line of debugger command input (up to and excluding the next
+    /// `\n`) through the same `R` used for `,`, returning `None` on EOF with
+    /// nothing left to read. Callers must read debugger commands through
+    /// this rather than opening their own handle onto the same stream: `,`
+    /// and the command prompt would otherwise each buffer independently and
+    /// steal bytes from each other on a shared stdin.
+    pub fn read_command_line(&mut self) -> Result<Option<String>, BfError> {
+        let mut line = std::vec::Vec::new();
+        loop {
+            match try!(self.runtime.input.read_byte()) {
+                Some(b'\n') => break,
+                Some(b) => line.push(b),
+                None if line.is_empty() => return Ok(None),
+                None => break,
+            }
+        }
+        Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+    }
+}
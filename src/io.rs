@@ -0,0 +1,86 @@
+//! Byte-stream I/O abstraction so the interpreter core doesn't have to call
+//! `std::io` directly, letting it run under `#![no_std]` (e.g. embedded in
+//! firmware over memory-mapped serial I/O) once an `Inst` IR is supplied.
+
+/// Error returned by [`BfRead`] / [`BfWrite`] implementations.
+#[derive(Debug)]
+pub enum BfError {
+    /// The underlying byte stream failed to read or write.
+    Io,
+}
+
+/// A byte-stream source for Brainfuck's `,` instruction.
+pub trait BfRead {
+    /// Reads the next input byte, or `Ok(None)` once the stream is exhausted.
+    fn read_byte(&mut self) -> Result<Option<u8>, BfError>;
+}
+
+/// A byte-stream sink for Brainfuck's `.` instruction.
+pub trait BfWrite {
+    fn write_byte(&mut self, b: u8) -> Result<(), BfError>;
+
+    /// Flushes any buffered output. Defaults to a no-op for sinks that don't buffer.
+    fn flush(&mut self) -> Result<(), BfError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_io {
+    use super::{BfError, BfRead, BfWrite};
+    use std::io::{self, BufReader, BufWriter, Read, Write};
+
+    /// Buffered [`BfRead`] over `std::io::stdin`.
+    pub struct StdReader(BufReader<io::Stdin>);
+
+    impl StdReader {
+        pub fn new() -> Self {
+            StdReader(BufReader::new(io::stdin()))
+        }
+    }
+
+    impl Default for StdReader {
+        fn default() -> Self {
+            StdReader::new()
+        }
+    }
+
+    impl BfRead for StdReader {
+        fn read_byte(&mut self) -> Result<Option<u8>, BfError> {
+            let mut buf = [0u8; 1];
+            match self.0.read_exact(&mut buf) {
+                Ok(()) => Ok(Some(buf[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                Err(_) => Err(BfError::Io),
+            }
+        }
+    }
+
+    /// Buffered [`BfWrite`] over `std::io::stdout`.
+    pub struct StdWriter(BufWriter<io::Stdout>);
+
+    impl StdWriter {
+        pub fn new() -> Self {
+            StdWriter(BufWriter::new(io::stdout()))
+        }
+    }
+
+    impl Default for StdWriter {
+        fn default() -> Self {
+            StdWriter::new()
+        }
+    }
+
+    impl BfWrite for StdWriter {
+        fn write_byte(&mut self, b: u8) -> Result<(), BfError> {
+            self.0.write_all(&[b]).map_err(|_| BfError::Io)
+        }
+
+        fn flush(&mut self) -> Result<(), BfError> {
+            self.0.flush().map_err(|_| BfError::Io)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::std_io::{StdReader, StdWriter};
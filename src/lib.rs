@@ -0,0 +1,749 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+// This crate consistently uses `try!` (its era predates `?`/edition 2018);
+// the compiler's blanket "prefer `?`" suggestion isn't useful here.
+#![allow(deprecated)]
+
+// Only declared under `std`: edition 2015 doesn't implicitly bring `core`
+// into scope for a normal (non-`no_std`) crate, so this is needed to resolve
+// `core::num::Wrapping` below. `#![no_std]` (applied when `std` is off)
+// already injects `extern crate core` itself, so declaring it again there
+// would be a duplicate (E0259).
+#[cfg(feature = "std")]
+extern crate core;
+
+mod io;
+#[cfg(feature = "std")]
+mod debugger;
+
+use core::num::Wrapping;
+
+pub use io::{BfError, BfRead, BfWrite};
+#[cfg(feature = "std")]
+pub use io::{StdReader, StdWriter};
+#[cfg(feature = "std")]
+pub use debugger::Debugger;
+
+#[cfg(feature = "std")]
+pub const PROGRAM_MEMORY: usize = 30000;
+
+/// A single coalesced IR instruction. `Program::new` compiles raw Brainfuck
+/// source down to a `Vec<Inst>` by run-length-coalescing adjacent identical
+/// operators, so `ProgramRuntime::step` can apply a whole run of `+`/`-`/`<`/`>`
+/// in one go instead of dispatching one byte at a time. Embedders targeting
+/// `#![no_std]` can skip `Program` entirely and supply an `&[Inst]` compiled
+/// ahead of time (e.g. on a host build, or hand-written as a `const` array).
+#[derive(Debug, Clone, Copy)]
+pub enum Inst {
+    /// Net delta of a run of `+`/`-`, applied via [`Cell::wrapping_add_delta`]
+    /// so it wraps correctly regardless of the tape's cell width.
+    Add(i32),
+    Move(isize),
+    Out(u32),
+    In(u32),
+    JumpIfZero(usize),
+    JumpIfNonZero(usize),
+    /// Peephole rewrite of `[-]` / `[+]`: zero the current cell directly.
+    Clear,
+    /// Peephole rewrite of `[>]` / `[<]`: advance the pointer by the given
+    /// (possibly negative) stride until the cell it lands on is zero.
+    ScanZero(isize),
+}
+
+#[cfg(feature = "std")]
+struct Program {
+    code: std::vec::Vec<Inst>,
+    /// IR indices marked by a `#` debug breakpoint char in the source, only
+    /// populated when compiled with `debug_mode` enabled.
+    breakpoints: std::vec::Vec<usize>,
+}
+
+/// Error compiling Brainfuck source into an `Inst` IR. Public only because it
+/// appears inside [`BrainfuckError`]; `Program` itself stays private.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ProgramError {
+    LoopBeginningWithoutEnd,
+    LoopEndWithoutBeginning,
+}
+
+#[cfg(feature = "std")]
+impl Program {
+    fn is_valid_bchar(bchar: u8, debug_mode: bool) -> bool {
+        match bchar {
+            b'>' | b'<' | b'+' | b'-' | b'.' | b',' | b'[' | b']' => true,
+            b'#' => debug_mode,
+            _ => false,
+        }
+    }
+
+    fn new(input_code: &[u8], debug_mode: bool) -> Result<Self, ProgramError> {
+        // strip out non-code characters before compiling
+        let bchars: std::vec::Vec<u8> =
+            input_code.iter().cloned().filter(|&b| Program::is_valid_bchar(b, debug_mode)).collect();
+
+        let mut code: std::vec::Vec<Inst> = std::vec::Vec::new();
+        let mut loop_stack: std::vec::Vec<usize> = std::vec::Vec::new();
+        let mut breakpoints: std::vec::Vec<usize> = std::vec::Vec::new();
+        let mut i = 0;
+        while i < bchars.len() {
+            match bchars[i] {
+                b'#' => {
+                    breakpoints.push(code.len());
+                    i += 1;
+                },
+                b'+' | b'-' => {
+                    let mut delta: i32 = 0;
+                    while i < bchars.len() && (bchars[i] == b'+' || bchars[i] == b'-') {
+                        delta += if bchars[i] == b'+' { 1 } else { -1 };
+                        i += 1;
+                    }
+                    code.push(Inst::Add(delta));
+                },
+                b'<' | b'>' => {
+                    let mut delta: isize = 0;
+                    while i < bchars.len() && (bchars[i] == b'<' || bchars[i] == b'>') {
+                        delta += if bchars[i] == b'>' { 1 } else { -1 };
+                        i += 1;
+                    }
+                    code.push(Inst::Move(delta));
+                },
+                b'.' => {
+                    let mut count: u32 = 0;
+                    while i < bchars.len() && bchars[i] == b'.' {
+                        count += 1;
+                        i += 1;
+                    }
+                    code.push(Inst::Out(count));
+                },
+                b',' => {
+                    let mut count: u32 = 0;
+                    while i < bchars.len() && bchars[i] == b',' {
+                        count += 1;
+                        i += 1;
+                    }
+                    code.push(Inst::In(count));
+                },
+                b'[' => {
+                    loop_stack.push(code.len());
+                    code.push(Inst::JumpIfZero(0)); // backpatched once the matching ] is found
+                    i += 1;
+                },
+                b']' => {
+                    let open_idx = match loop_stack.pop() {
+                        Some(open_idx) => open_idx,
+                        None => return Err(ProgramError::LoopEndWithoutBeginning),
+                    };
+                    let close_idx = code.len();
+
+                    // peephole: a loop whose only body instruction is an odd
+                    // Add (e.g. `[-]`/`[+]`) always zeroes the cell; one whose
+                    // only body instruction is a Move (e.g. `[>]`/`[<]`) just
+                    // scans for the next zero cell. Skipped if a `#` landed
+                    // inside the loop body: collapsing it would leave that
+                    // breakpoint's IR index aliasing whatever instruction ends
+                    // up there after the rewrite.
+                    let body = &code[open_idx + 1..close_idx];
+                    // `bp == close_idx` is a `#` sitting right before this `]`,
+                    // which is still inside the body.
+                    let has_breakpoint_in_body = breakpoints.iter().any(|&bp| bp > open_idx && bp <= close_idx);
+                    match body {
+                        [Inst::Add(n)] if n % 2 != 0 && !has_breakpoint_in_body => {
+                            code.truncate(open_idx);
+                            code.push(Inst::Clear);
+                        },
+                        [Inst::Move(stride)] if !has_breakpoint_in_body => {
+                            let stride = *stride;
+                            code.truncate(open_idx);
+                            code.push(Inst::ScanZero(stride));
+                        },
+                        _ => {
+                            code.push(Inst::JumpIfNonZero(open_idx + 1));
+                            code[open_idx] = Inst::JumpIfZero(close_idx + 1);
+                        },
+                    }
+                    i += 1;
+                },
+                bchar => debug_assert!(!Program::is_valid_bchar(bchar, debug_mode), "Non-code char wasn't stripped!"),
+            }
+        }
+
+        if !loop_stack.is_empty() {
+            return Err(ProgramError::LoopBeginningWithoutEnd);
+        }
+
+        Ok(Program { code, breakpoints })
+    }
+}
+
+#[derive(Debug)]
+pub enum ProgramRuntimeError {
+    ReadAccessViolation,
+    WriteAccessViolation,
+    Io(BfError),
+}
+
+impl From<BfError> for ProgramRuntimeError {
+    fn from(err: BfError) -> Self {
+        ProgramRuntimeError::Io(err)
+    }
+}
+
+/// A tape cell's width. Implemented for `u8`, `u16` and `u32` so the same
+/// `Inst` IR can be run over wider cells without every program needing to
+/// pack/unpack bytes itself; [`Inst::Add`] deltas are always applied modulo
+/// the cell's own width.
+pub trait Cell: Copy {
+    const ZERO: Self;
+
+    /// Adds `delta` to `self`, wrapping modulo the cell's width.
+    fn wrapping_add_delta(self, delta: i32) -> Self;
+
+    fn is_zero(self) -> bool;
+
+    /// Truncates to the low byte for `.`'s output.
+    fn to_io_byte(self) -> u8;
+
+    /// Zero-extends a byte read by `,` up to the cell's width.
+    fn from_io_byte(b: u8) -> Self;
+
+    /// The value `,` writes under [`EofBehavior::NegativeOne`]: every bit of
+    /// the cell set, i.e. the cell's width reinterpreting `-1` as unsigned.
+    fn negative_one() -> Self {
+        Self::ZERO.wrapping_add_delta(-1)
+    }
+}
+
+impl Cell for u8 {
+    const ZERO: u8 = 0;
+
+    fn wrapping_add_delta(self, delta: i32) -> u8 {
+        self.wrapping_add(delta as u8)
+    }
+
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+
+    fn to_io_byte(self) -> u8 {
+        self
+    }
+
+    fn from_io_byte(b: u8) -> u8 {
+        b
+    }
+}
+
+impl Cell for u16 {
+    const ZERO: u16 = 0;
+
+    fn wrapping_add_delta(self, delta: i32) -> u16 {
+        self.wrapping_add(delta as u16)
+    }
+
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+
+    fn to_io_byte(self) -> u8 {
+        self as u8
+    }
+
+    fn from_io_byte(b: u8) -> u16 {
+        b as u16
+    }
+}
+
+impl Cell for u32 {
+    const ZERO: u32 = 0;
+
+    fn wrapping_add_delta(self, delta: i32) -> u32 {
+        self.wrapping_add(delta as u32)
+    }
+
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+
+    fn to_io_byte(self) -> u8 {
+        self as u8
+    }
+
+    fn from_io_byte(b: u8) -> u32 {
+        b as u32
+    }
+}
+
+/// What happens when the memory pointer moves outside of the tape's current
+/// bounds.
+#[derive(Debug, Clone, Copy)]
+pub enum PointerMode {
+    /// Wrap around to the other end of the tape, as if it were circular.
+    Wrap,
+    /// Fail with a [`ProgramRuntimeError`] access violation instead of moving
+    /// out of bounds.
+    Error,
+    /// Grow the tape on demand to cover the new pointer position. Only takes
+    /// effect when the backing [`Tape`] actually supports growing (the
+    /// `std::vec::Vec` impl does; a fixed `&mut [C]` slice can't grow, so it
+    /// falls back to an access violation once the pointer leaves its bounds).
+    GrowDynamic,
+}
+
+/// A memory tape addressable by [`ProgramRuntime::step`]. Implemented for a
+/// plain `&mut [C]` (fixed-length, `#![no_std]`-friendly) and, under `std`,
+/// for `std::vec::Vec<C>` (grows on demand under [`PointerMode::GrowDynamic`],
+/// and otherwise bounds-checks like a fixed-length tape would). `mode` is
+/// passed into every access, rather than fixed by the `Tape` impl, so the
+/// same `Vec<C>` can back any [`PointerMode`] the caller asks for.
+pub trait Tape<C: Cell> {
+    fn get(&mut self, idx: usize, mode: PointerMode) -> Result<C, ProgramRuntimeError>;
+    fn set(&mut self, idx: usize, val: C, mode: PointerMode) -> Result<(), ProgramRuntimeError>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<C: Cell> Tape<C> for [C] {
+    fn get(&mut self, idx: usize, _mode: PointerMode) -> Result<C, ProgramRuntimeError> {
+        if idx < <[C]>::len(self) {
+            Ok(self[idx])
+        } else {
+            Err(ProgramRuntimeError::ReadAccessViolation)
+        }
+    }
+
+    fn set(&mut self, idx: usize, val: C, _mode: PointerMode) -> Result<(), ProgramRuntimeError> {
+        if idx < <[C]>::len(self) {
+            self[idx] = val;
+            Ok(())
+        } else {
+            Err(ProgramRuntimeError::WriteAccessViolation)
+        }
+    }
+
+    fn len(&self) -> usize {
+        <[C]>::len(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C: Cell> Tape<C> for std::vec::Vec<C> {
+    fn get(&mut self, idx: usize, mode: PointerMode) -> Result<C, ProgramRuntimeError> {
+        if idx >= std::vec::Vec::len(self) {
+            match mode {
+                PointerMode::GrowDynamic => self.resize(idx + 1, C::ZERO),
+                PointerMode::Wrap | PointerMode::Error => return Err(ProgramRuntimeError::ReadAccessViolation),
+            }
+        }
+        Ok(self[idx])
+    }
+
+    fn set(&mut self, idx: usize, val: C, mode: PointerMode) -> Result<(), ProgramRuntimeError> {
+        if idx >= std::vec::Vec::len(self) {
+            match mode {
+                PointerMode::GrowDynamic => self.resize(idx + 1, C::ZERO),
+                PointerMode::Wrap | PointerMode::Error => return Err(ProgramRuntimeError::WriteAccessViolation),
+            }
+        }
+        self[idx] = val;
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        std::vec::Vec::len(self)
+    }
+}
+
+/// Resolves the tape index the pointer lands on after moving by `stride`
+/// cells, according to `mode`.
+fn move_ptr<C: Cell, T: Tape<C> + ?Sized>(
+    tape: &T,
+    ptr: usize,
+    stride: isize,
+    mode: PointerMode,
+) -> Result<usize, ProgramRuntimeError> {
+    let raw = ptr as i64 + stride as i64;
+    match mode {
+        PointerMode::Wrap => {
+            let len = tape.len() as i64;
+            if len == 0 {
+                return Err(ProgramRuntimeError::WriteAccessViolation);
+            }
+            Ok((((raw % len) + len) % len) as usize)
+        },
+        PointerMode::Error => {
+            if raw < 0 || raw as usize >= tape.len() {
+                Err(ProgramRuntimeError::WriteAccessViolation)
+            } else {
+                Ok(raw as usize)
+            }
+        },
+        PointerMode::GrowDynamic => {
+            if raw < 0 {
+                Err(ProgramRuntimeError::WriteAccessViolation)
+            } else {
+                Ok(raw as usize)
+            }
+        },
+    }
+}
+
+#[derive(Debug)]
+enum ProgramRuntimeStatus {
+    RanInstruction,
+    EndOfProgram,
+}
+
+/// What a `,` should write to the current cell once the input stream is
+/// exhausted, instead of aborting the program.
+#[derive(Debug, Clone, Copy)]
+pub enum EofBehavior {
+    LeaveUnchanged,
+    Zero,
+    NegativeOne,
+}
+
+/// Error type returned by the public [`eval`] / [`eval_mem`] entry points,
+/// unifying program loading errors and runtime execution errors.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum BrainfuckError {
+    Program(ProgramError),
+    Runtime(ProgramRuntimeError),
+}
+
+#[cfg(feature = "std")]
+impl From<ProgramError> for BrainfuckError {
+    fn from(err: ProgramError) -> Self {
+        BrainfuckError::Program(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ProgramRuntimeError> for BrainfuckError {
+    fn from(err: ProgramRuntimeError) -> Self {
+        BrainfuckError::Runtime(err)
+    }
+}
+
+/// Drives an `Inst` IR over memory, a pointer and I/O streams supplied by the
+/// caller, so the same runtime can be resumed across multiple invocations.
+/// Generic over [`BfRead`]/[`BfWrite`] so it never touches `std::io` directly,
+/// and works under `#![no_std]`.
+struct ProgramRuntime<R: BfRead, W: BfWrite> {
+    pc: Wrapping<usize>,
+    input: R,
+    output: W,
+    eof_behavior: EofBehavior,
+    pointer_mode: PointerMode,
+}
+
+impl<R: BfRead, W: BfWrite> ProgramRuntime<R, W> {
+    fn new(input: R, output: W, eof_behavior: EofBehavior, pointer_mode: PointerMode) -> Self {
+        ProgramRuntime {
+            pc: Wrapping(0),
+            input,
+            output,
+            eof_behavior,
+            pointer_mode,
+        }
+    }
+
+    fn step<C: Cell, T: Tape<C> + ?Sized>(
+        &mut self,
+        code: &[Inst],
+        tape: &mut T,
+        mem_ptr: &mut usize,
+    ) -> Result<ProgramRuntimeStatus, ProgramRuntimeError> {
+        let pc = self.pc.0;
+        let mut next_pc = pc + 1;
+
+        if pc >= code.len() {
+            try!(self.output.flush());
+            return Ok(ProgramRuntimeStatus::EndOfProgram);
+        }
+
+        match code[pc] {
+            Inst::Add(delta) => {
+                let val = try!(tape.get(*mem_ptr, self.pointer_mode));
+                try!(tape.set(*mem_ptr, val.wrapping_add_delta(delta), self.pointer_mode));
+            },
+            Inst::Move(stride) => {
+                *mem_ptr = try!(move_ptr(&*tape, *mem_ptr, stride, self.pointer_mode));
+            },
+            Inst::Out(count) => {
+                let val = try!(tape.get(*mem_ptr, self.pointer_mode)).to_io_byte();
+                for _ in 0..count {
+                    try!(self.output.write_byte(val));
+                }
+            },
+            Inst::In(count) => {
+                for _ in 0..count {
+                    match try!(self.input.read_byte()) {
+                        Some(byte) => { try!(tape.set(*mem_ptr, C::from_io_byte(byte), self.pointer_mode)); },
+                        None => match self.eof_behavior {
+                            EofBehavior::LeaveUnchanged => {},
+                            EofBehavior::Zero => { try!(tape.set(*mem_ptr, C::ZERO, self.pointer_mode)); },
+                            EofBehavior::NegativeOne => { try!(tape.set(*mem_ptr, C::negative_one(), self.pointer_mode)); },
+                        },
+                    }
+                }
+            },
+            Inst::JumpIfZero(target) => {
+                if (try!(tape.get(*mem_ptr, self.pointer_mode))).is_zero() {
+                    next_pc = target;
+                }
+            },
+            Inst::JumpIfNonZero(target) => {
+                if !(try!(tape.get(*mem_ptr, self.pointer_mode))).is_zero() {
+                    next_pc = target;
+                }
+            },
+            Inst::Clear => try!(tape.set(*mem_ptr, C::ZERO, self.pointer_mode)),
+            Inst::ScanZero(stride) => {
+                while !(try!(tape.get(*mem_ptr, self.pointer_mode))).is_zero() {
+                    *mem_ptr = try!(move_ptr(&*tape, *mem_ptr, stride, self.pointer_mode));
+                }
+            },
+        }
+
+        self.pc = Wrapping(next_pc);
+        Ok(ProgramRuntimeStatus::RanInstruction)
+    }
+}
+
+/// Runs a pre-compiled `Inst` IR against a tape, a pointer and I/O streams
+/// supplied by the caller. This is the `#![no_std]`-friendly entry point: it
+/// never touches `std::io`, and works over a plain `&mut [C]` tape without
+/// allocating, so it can be embedded in firmware once the IR has been
+/// compiled (e.g. on a host build). Pass a `std::vec::Vec<C>` instead if
+/// `pointer_mode` is [`PointerMode::GrowDynamic`] and the tape should grow.
+pub fn run_ir<C: Cell, T: Tape<C> + ?Sized, R: BfRead, W: BfWrite>(
+    code: &[Inst],
+    tape: &mut T,
+    mem_ptr: &mut usize,
+    input: R,
+    output: W,
+    eof_behavior: EofBehavior,
+    pointer_mode: PointerMode,
+) -> Result<(), ProgramRuntimeError> {
+    let mut runtime = ProgramRuntime::new(input, output, eof_behavior, pointer_mode);
+    loop {
+        let status = try!(runtime.step(code, tape, mem_ptr));
+        if let ProgramRuntimeStatus::EndOfProgram = status {
+            return Ok(());
+        }
+    }
+}
+
+/// Parses and runs `code` against a fresh, zeroed tape of `PROGRAM_MEMORY`
+/// `u8` cells wrapped at either end. `code` is a raw byte stream rather than
+/// `&str`, since Brainfuck source has no encoding of its own and non-code
+/// bytes (e.g. extended-ASCII art in comments) are just stripped out, not
+/// decoded. Use [`eval_mem`] instead if you need to supply or reuse your own
+/// tape across multiple runs, [`eval_with_eof_behavior`] to control what `,`
+/// does once input runs out, or [`eval_mem_with_opts`] for wider cells or a
+/// different [`PointerMode`].
+#[cfg(feature = "std")]
+pub fn eval(code: &[u8]) -> Result<(), BrainfuckError> {
+    eval_with_eof_behavior(code, EofBehavior::LeaveUnchanged)
+}
+
+/// Like [`eval`], but lets the caller choose what `,` writes to the current
+/// cell once the input stream is exhausted.
+#[cfg(feature = "std")]
+pub fn eval_with_eof_behavior(code: &[u8], eof_behavior: EofBehavior) -> Result<(), BrainfuckError> {
+    let mut mem = [0u8; PROGRAM_MEMORY];
+    let mut mem_ptr = 0usize;
+    eval_mem_with_opts(code, &mut mem[..], &mut mem_ptr, eof_behavior, PointerMode::Wrap)
+}
+
+/// Parses and runs `code` against a caller-supplied `u8` tape and memory
+/// pointer, so callers embedding the interpreter can preserve memory and
+/// pointer state across multiple invocations.
+#[cfg(feature = "std")]
+pub fn eval_mem(code: &[u8], mem: &mut [u8], mem_ptr: &mut usize) -> Result<(), BrainfuckError> {
+    eval_mem_with_eof_behavior(code, mem, mem_ptr, EofBehavior::LeaveUnchanged)
+}
+
+/// Like [`eval_mem`], but lets the caller choose what `,` writes to the
+/// current cell once the input stream is exhausted.
+#[cfg(feature = "std")]
+pub fn eval_mem_with_eof_behavior(
+    code: &[u8],
+    mem: &mut [u8],
+    mem_ptr: &mut usize,
+    eof_behavior: EofBehavior,
+) -> Result<(), BrainfuckError> {
+    eval_mem_with_opts(code, mem, mem_ptr, eof_behavior, PointerMode::Wrap)
+}
+
+/// Parses and runs `code` against a caller-supplied tape, memory pointer,
+/// EOF behavior and [`PointerMode`]. This is the most general `std` entry
+/// point: pick `C` to be `u8`, `u16` or `u32` for a wider cell, and pass a
+/// `std::vec::Vec<C>` as `tape` instead of a fixed slice if `pointer_mode` is
+/// [`PointerMode::GrowDynamic`] and the tape should grow on demand.
+#[cfg(feature = "std")]
+pub fn eval_mem_with_opts<C: Cell, T: Tape<C> + ?Sized>(
+    code: &[u8],
+    tape: &mut T,
+    mem_ptr: &mut usize,
+    eof_behavior: EofBehavior,
+    pointer_mode: PointerMode,
+) -> Result<(), BrainfuckError> {
+    let program = try!(Program::new(code, false));
+    try!(run_ir(&program.code, tape, mem_ptr, StdReader::new(), StdWriter::new(), eof_behavior, pointer_mode));
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Feeds fixed bytes to `,`, then reports EOF.
+    struct VecReader {
+        bytes: std::vec::Vec<u8>,
+        pos: usize,
+    }
+
+    impl BfRead for VecReader {
+        fn read_byte(&mut self) -> Result<Option<u8>, BfError> {
+            if self.pos < self.bytes.len() {
+                let b = self.bytes[self.pos];
+                self.pos += 1;
+                Ok(Some(b))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    /// Collects `.` output into a shared buffer so the test can inspect it
+    /// after `run_ir` (which takes its writer by value) returns.
+    struct VecWriter(Rc<RefCell<std::vec::Vec<u8>>>);
+
+    impl BfWrite for VecWriter {
+        fn write_byte(&mut self, b: u8) -> Result<(), BfError> {
+            self.0.borrow_mut().push(b);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn eof_behavior_controls_comma_at_eof() {
+        let program = Program::new(b",", false).unwrap();
+        for (eof_behavior, expected) in [
+            (EofBehavior::LeaveUnchanged, 42u8),
+            (EofBehavior::Zero, 0u8),
+            (EofBehavior::NegativeOne, 0xFFu8),
+        ] {
+            let mut mem = [42u8; 1];
+            let mut mem_ptr = 0usize;
+            let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+            let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+            run_ir(&program.code, &mut mem[..], &mut mem_ptr, input, output, eof_behavior, PointerMode::Wrap).unwrap();
+            assert_eq!(mem[0], expected, "{:?}", eof_behavior);
+        }
+    }
+
+    #[test]
+    fn clear_and_scan_zero_are_peephole_rewritten() {
+        assert!(matches!(Program::new(b"[-]", false).unwrap().code[..], [Inst::Clear]));
+        assert!(matches!(Program::new(b"[+]", false).unwrap().code[..], [Inst::Clear]));
+        assert!(matches!(Program::new(b"[>]", false).unwrap().code[..], [Inst::ScanZero(1)]));
+        assert!(matches!(Program::new(b"[<]", false).unwrap().code[..], [Inst::ScanZero(-1)]));
+    }
+
+    #[test]
+    fn scan_zero_advances_to_the_next_zero_cell() {
+        let program = Program::new(b"+[>]", false).unwrap();
+        let mut mem = [1u8, 1, 1, 0, 1];
+        let mut mem_ptr = 0usize;
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        run_ir(&program.code, &mut mem[..], &mut mem_ptr, input, output, EofBehavior::LeaveUnchanged, PointerMode::Wrap)
+            .unwrap();
+        assert_eq!(mem_ptr, 3);
+    }
+
+    #[test]
+    fn pointer_mode_wrap_wraps_around_tape_ends() {
+        let program = Program::new(b">>>", false).unwrap();
+        let mut mem = [0u8; 3];
+        let mut mem_ptr = 0usize;
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        run_ir(&program.code, &mut mem[..], &mut mem_ptr, input, output, EofBehavior::LeaveUnchanged, PointerMode::Wrap)
+            .unwrap();
+        assert_eq!(mem_ptr, 0);
+    }
+
+    #[test]
+    fn pointer_mode_error_rejects_out_of_bounds_moves() {
+        let program = Program::new(b">>>", false).unwrap();
+        let mut mem = [0u8; 3];
+        let mut mem_ptr = 0usize;
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        let result =
+            run_ir(&program.code, &mut mem[..], &mut mem_ptr, input, output, EofBehavior::LeaveUnchanged, PointerMode::Error);
+        assert!(matches!(result, Err(ProgramRuntimeError::WriteAccessViolation)));
+    }
+
+    #[test]
+    fn pointer_mode_grow_dynamic_grows_a_vec_tape() {
+        let program = Program::new(b">>>>>+", false).unwrap();
+        let mut mem: std::vec::Vec<u8> = std::vec::Vec::new();
+        let mut mem_ptr = 0usize;
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        run_ir(
+            &program.code,
+            &mut mem,
+            &mut mem_ptr,
+            input,
+            output,
+            EofBehavior::LeaveUnchanged,
+            PointerMode::GrowDynamic,
+        )
+        .unwrap();
+        assert_eq!(mem.len(), 6);
+        assert_eq!(mem[5], 1);
+    }
+
+    #[test]
+    fn pointer_mode_error_on_vec_tape_does_not_silently_grow() {
+        // A Vec<C> tape paired with PointerMode::Error must bounds-check like
+        // a fixed tape would, not fall back to GrowDynamic-style resizing.
+        let program = Program::new(b"+", false).unwrap();
+        let mut mem: std::vec::Vec<u8> = std::vec::Vec::new();
+        let mut mem_ptr = 0usize;
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        let result =
+            run_ir(&program.code, &mut mem, &mut mem_ptr, input, output, EofBehavior::LeaveUnchanged, PointerMode::Error);
+        assert!(matches!(result, Err(ProgramRuntimeError::ReadAccessViolation)));
+        assert!(mem.is_empty());
+    }
+
+    #[test]
+    fn breakpoint_inside_a_collapsed_loop_suppresses_the_peephole_rewrite() {
+        // `[-]` would normally collapse to a single Clear instruction, but a
+        // `#` inside it must still land on (an IR instruction standing in
+        // for) the `-`, not get stranded on whatever ends up at its stale
+        // pre-rewrite index once the loop shrinks.
+        let program = Program::new(b"+[#-]+", true).unwrap();
+        assert_eq!(program.breakpoints, vec![2]);
+        assert!(!matches!(program.code[2], Inst::Clear));
+
+        let input = VecReader { bytes: std::vec::Vec::new(), pos: 0 };
+        let output = VecWriter(Rc::new(RefCell::new(std::vec::Vec::new())));
+        let mut debugger = Debugger::new(b"+[#-]+", 1, input, output, EofBehavior::LeaveUnchanged).unwrap();
+        let hit_pc = debugger.cont().unwrap();
+        assert_eq!(hit_pc, Some(2));
+    }
+}
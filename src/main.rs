@@ -1,168 +1,116 @@
-use std::{env, io};
-use std::collections::HashMap;
-use std::num::Wrapping;
-use std::fs::File;
-use std::io::Read;
-
-struct Program {
-    code: Vec<u8>,
-    loop_links: HashMap<usize, usize>,
-}
+extern crate rustedbrain;
 
-#[derive(Debug)]
-enum ProgramError {
-    LoopBeginningWithoutEnd,
-    LoopEndWithoutBeginning,
-}
-
-impl Program {
-    fn is_valid_bchar(bchar: u8) -> bool {
-        match bchar {
-            b'>' | b'<' | b'+' | b'-' | b'.' | b',' | b'[' | b']' => true,
-            _ => false,
-        }
-    }
-
-    fn new(input_code: &Vec<u8>) -> Result<Self, ProgramError> {
-        let mut program = Program { code: input_code.clone(), loop_links: HashMap::new() };
-        program.code.retain(|&bchar| Program::is_valid_bchar(bchar)); // strip out non-code characters
-
-        // resolve loop links
-        let mut unfinished_loop_links = Vec::new();
-        for (i, &bchar) in program.code.iter().enumerate() {
-            if bchar == b'[' {
-                unfinished_loop_links.push(i);
-            } else if bchar == b']' {
-                if unfinished_loop_links.len() > 0 {
-                    let loop_beginning = unfinished_loop_links.pop().unwrap();
-                    program.loop_links.insert(loop_beginning, i);
-                    program.loop_links.insert(i, loop_beginning);
-                } else {
-                    return Err(ProgramError::LoopEndWithoutBeginning);
-                }
-            }
-        }
-
-        if unfinished_loop_links.len() > 0 {
-            return Err(ProgramError::LoopBeginningWithoutEnd);
-        }
-        
-        Ok(program)
+use rustedbrain::{Cell, Debugger, EofBehavior, PointerMode, StdReader, StdWriter, PROGRAM_MEMORY};
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+fn parse_eof_behavior(arg: &str) -> EofBehavior {
+    match arg {
+        "unchanged" => EofBehavior::LeaveUnchanged,
+        "zero" => EofBehavior::Zero,
+        "neg-one" => EofBehavior::NegativeOne,
+        _ => panic!("Unknown --eof value '{}' (expected unchanged, zero or neg-one)", arg),
     }
 }
 
-const PROGRAM_MEMORY: usize = 30000;
-
-struct ProgramRuntime {
-    pc: Wrapping<usize>,
-    mem: [Wrapping<u8>; PROGRAM_MEMORY],
-    mem_ptr: Wrapping<usize>,
-}
-
-#[derive(Debug)]
-enum ProgramRuntimeError {
-    ReadAccessViolation,
-    WriteAccessViolation,
-}
-
-#[derive(Debug)]
-enum ProgramRuntimeStatus {
-    RanInstructionAtPC(usize),
-    EndOfProgram,
-}
-
-impl ProgramRuntime {
-    fn new() -> Self {
-        ProgramRuntime {
-            pc: Wrapping(0),
-            mem: [Wrapping(0); PROGRAM_MEMORY],
-            mem_ptr: Wrapping(0),
-        }
-    }
-
-    fn read_mem(&self, loc: usize) -> Result<u8, ProgramRuntimeError> {
-        if loc < self.mem.len() {
-            Ok(self.mem[loc].0)
-        } else {
-            Err(ProgramRuntimeError::ReadAccessViolation)
-        }
-    }
-
-    fn read_mem_at_ptr(&self) -> Result<u8, ProgramRuntimeError> {
-        self.read_mem(self.mem_ptr.0)
-    }
-
-    fn write_mem(&mut self, loc: usize, val: u8) -> Result<(), ProgramRuntimeError> {
-        if loc < self.mem.len() {
-            self.mem[loc] = Wrapping(val);
-            Ok(())
-        } else {
-            Err(ProgramRuntimeError::WriteAccessViolation)
-        }
-    }
-    
-    fn write_mem_at_ptr(&mut self, val: u8) -> Result<(), ProgramRuntimeError> {
-        let loc = self.mem_ptr.0;
-        self.write_mem(loc, val)
+fn parse_pointer_mode(arg: &str) -> PointerMode {
+    match arg {
+        "wrap" => PointerMode::Wrap,
+        "error" => PointerMode::Error,
+        "grow" => PointerMode::GrowDynamic,
+        _ => panic!("Unknown --pointer-mode value '{}' (expected wrap, error or grow)", arg),
     }
+}
 
-    fn inc_mem_at_ptr(&mut self) -> Result<u8, ProgramRuntimeError> {
-        let loc = self.mem_ptr.0;
-        if loc < self.mem.len() {
-            self.mem[loc] += Wrapping(1);
-            Ok(self.mem[loc].0)
-        } else {
-            Err(ProgramRuntimeError::WriteAccessViolation)
-        }
+/// Parses and runs `file_data` over a tape of `C` cells, sized and bounded
+/// according to `mem_len` and `pointer_mode`. Growable tapes start out empty
+/// and grow on demand, so `mem_len` is ignored under [`PointerMode::GrowDynamic`].
+fn run<C: Cell>(file_data: &[u8], mem_len: usize, eof_behavior: EofBehavior, pointer_mode: PointerMode) {
+    let mut mem_ptr = 0usize;
+    match pointer_mode {
+        PointerMode::GrowDynamic => {
+            let mut mem: Vec<C> = Vec::new();
+            rustedbrain::eval_mem_with_opts::<C, _>(file_data, &mut mem, &mut mem_ptr, eof_behavior, pointer_mode)
+                .expect("Program runtime execution error");
+        },
+        PointerMode::Wrap | PointerMode::Error => {
+            let mut mem: Vec<C> = vec![C::ZERO; mem_len];
+            rustedbrain::eval_mem_with_opts::<C, _>(file_data, &mut mem[..], &mut mem_ptr, eof_behavior, pointer_mode)
+                .expect("Program runtime execution error");
+        },
     }
+}
 
-    fn dec_mem_at_ptr(&mut self) -> Result<u8, ProgramRuntimeError> {
-        let loc = self.mem_ptr.0;
-        if loc < self.mem.len() {
-            self.mem[loc] -= Wrapping(1);
-            Ok(self.mem[loc].0)
-        } else {
-            Err(ProgramRuntimeError::WriteAccessViolation)
-        }
+/// Prints the debugger's current PC, decoded instruction and a window of the
+/// tape around the memory pointer.
+fn print_debugger_state(debugger: &Debugger<StdReader, StdWriter>) {
+    if debugger.is_finished() {
+        println!("[pc {}] program finished", debugger.pc());
+        return;
     }
 
-    fn step(&mut self, program: &Program) -> Result<ProgramRuntimeStatus, ProgramRuntimeError> {
-        let mut next_pc = self.pc + Wrapping(1);
-        let pc = self.pc.0;
+    println!("[pc {}] {:?}", debugger.pc(), debugger.current_inst().unwrap());
 
-        if pc >= program.code.len() {
-            return Ok(ProgramRuntimeStatus::EndOfProgram);
-        }
+    let (window, ptr_offset) = debugger.tape_window(4);
+    let cells: Vec<String> = window.iter().map(|c| format!("{:3}", c)).collect();
+    println!("mem_ptr {}: [{}]", debugger.mem_ptr(), cells.join(" "));
+    println!(" {}^", "    ".repeat(ptr_offset));
+}
 
-        match program.code[pc] {
-            b'>' => self.mem_ptr += Wrapping(1),
-            b'<' => self.mem_ptr -= Wrapping(1),
-            b'+' => { try!(self.inc_mem_at_ptr()); },
-            b'-' => { try!(self.dec_mem_at_ptr()); },
-            b'.' => print!("{}", try!(self.read_mem_at_ptr()) as char),
-            b',' => {
-                // read byte from stdin and store at ptr
-                let mut read_buf: [u8; 1] = [0];
-                io::stdin().read_exact(&mut read_buf).expect("Failed to read from stdin");
-                try!(self.write_mem_at_ptr(read_buf[0]));
+fn run_debugger(code: &[u8], mem_len: usize, eof_behavior: EofBehavior) {
+    let mut debugger = Debugger::new(code, mem_len, StdReader::new(), StdWriter::new(), eof_behavior)
+        .expect("Failed to load program");
+
+    println!("rustedbrain debugger. Commands: s(tep), c(ontinue), b <pc>, d <pc>, p(rint), q(uit)");
+    print_debugger_state(&debugger);
+
+    loop {
+        print!("(rbdb) ");
+        io::stdout().flush().expect("Failed to flush stdout");
+
+        // Read commands through the debugger's own input stream (the same
+        // one `,` reads from) rather than a separate `io::stdin()` handle:
+        // two independently-buffered readers over the same fd would each
+        // read ahead and steal bytes from the other.
+        let line = match debugger.read_command_line().expect("Failed to read debugger command") {
+            Some(line) => line,
+            None => break, // EOF on the command stream
+        };
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("s") | Some("step") => {
+                debugger.step().expect("Program runtime execution error");
+                print_debugger_state(&debugger);
             },
-            b'[' => {
-                // jump past matching ] if mem at ptr is 0
-                if try!(self.read_mem_at_ptr()) == 0 {
-                    next_pc = Wrapping(*program.loop_links.get(&pc).unwrap()) + Wrapping(1);
-                }
+            Some("c") | Some("continue") => {
+                debugger.cont().expect("Program runtime execution error");
+                print_debugger_state(&debugger);
             },
-            b']' => {
-                // jump back past matching [ if mem at ptr is NOT 0
-                if try!(self.read_mem_at_ptr()) != 0 {
-                    next_pc = Wrapping(*program.loop_links.get(&pc).unwrap()) + Wrapping(1);
-                }
+            Some("b") => match words.next().and_then(|arg| arg.parse().ok()) {
+                Some(pc) => {
+                    debugger.add_breakpoint(pc);
+                    println!("Breakpoint set at pc {}", pc);
+                },
+                None => println!("Usage: b <pc>"),
             },
-            bchar => debug_assert!(!Program::is_valid_bchar(bchar), "Non-code char wasn't stripped!"),
+            Some("d") => match words.next().and_then(|arg| arg.parse().ok()) {
+                Some(pc) => {
+                    debugger.remove_breakpoint(pc);
+                    println!("Breakpoint removed at pc {}", pc);
+                },
+                None => println!("Usage: d <pc>"),
+            },
+            Some("p") | Some("print") => print_debugger_state(&debugger),
+            Some("q") | Some("quit") => break,
+            Some(other) => println!("Unknown command '{}'", other),
+            None => {},
         }
 
-        self.pc = next_pc;
-        Ok(ProgramRuntimeStatus::RanInstructionAtPC(pc))
+        if debugger.is_finished() {
+            break;
+        }
     }
 }
 
@@ -174,21 +122,59 @@ fn main() {
         println!("rustedbrain - A Brainf*ck language interpreter written in Rust.");
         println!("Written by Sean Dewar (seandewar @ github). Version {}.",
                  option_env!("CARGO_PKG_VERSION").unwrap_or("[UNKNOWN]"));
-        println!("");
+        println!();
         println!("Usage:");
-        println!("  rustedbrain <file-path>           Run the script at <file-path>");
+        println!("  rustedbrain [--eof=<unchanged|zero|neg-one>] [--debug]");
+        println!("              [--cell=<8|16|32>] [--pointer-mode=<wrap|error|grow>]");
+        println!("              [--mem-len=<N>] <file-path>");
+        println!("                                     Run the script at <file-path>");
         println!("  rustedbrain | -h | -? | --help    Display this help message");
     } else {
+        let mut eof_behavior = EofBehavior::LeaveUnchanged;
+        let mut pointer_mode = PointerMode::Wrap;
+        let mut cell_width: u32 = 8;
+        let mut mem_len = PROGRAM_MEMORY;
+        let mut debug = false;
+        let mut file_path: Option<&String> = None;
+        for arg in &args[1..] {
+            if let Some(value) = arg.strip_prefix("--eof=") {
+                eof_behavior = parse_eof_behavior(value);
+            } else if let Some(value) = arg.strip_prefix("--pointer-mode=") {
+                pointer_mode = parse_pointer_mode(value);
+            } else if let Some(value) = arg.strip_prefix("--cell=") {
+                cell_width = value.parse().expect("<N> in --cell=<N> must be a number");
+            } else if let Some(value) = arg.strip_prefix("--mem-len=") {
+                mem_len = value.parse().expect("<N> in --mem-len=<N> must be a number");
+            } else if arg == "--debug" {
+                debug = true;
+            } else {
+                file_path = Some(arg);
+            }
+        }
+        let file_path = file_path.expect("Missing <file-path> argument");
+
+        // The debugger (see debugger.rs) always runs over u8 cells wrapped at
+        // the tape ends; it can't honor a different --cell or --pointer-mode,
+        // so refuse rather than silently running as if they'd been ignored.
+        if debug && cell_width != 8 {
+            panic!("--debug only supports --cell=8 (the debugger always runs over u8 cells)");
+        }
+        if debug && !matches!(pointer_mode, PointerMode::Wrap) {
+            panic!("--debug only supports --pointer-mode=wrap (the debugger always wraps)");
+        }
+
         let mut file_data = Vec::new();
-        let mut file = File::open(&args[1]).expect("Failed to open file");
+        let mut file = File::open(file_path).expect("Failed to open file");
         file.read_to_end(&mut file_data).expect("Failed to read file data");
 
-        let program = Program::new(&file_data).expect("Failed to load program");
-        let mut program_runtime = ProgramRuntime::new();
-        loop {
-            let runtime_status = program_runtime.step(&program).expect("Program runtime execution error");
-            if let ProgramRuntimeStatus::EndOfProgram = runtime_status {
-                break;
+        if debug {
+            run_debugger(&file_data, mem_len, eof_behavior);
+        } else {
+            match cell_width {
+                8 => run::<u8>(&file_data, mem_len, eof_behavior, pointer_mode),
+                16 => run::<u16>(&file_data, mem_len, eof_behavior, pointer_mode),
+                32 => run::<u32>(&file_data, mem_len, eof_behavior, pointer_mode),
+                _ => panic!("Unknown --cell value '{}' (expected 8, 16 or 32)", cell_width),
             }
         }
     }